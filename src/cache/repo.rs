@@ -0,0 +1,185 @@
+use std::str::FromStr;
+
+use crate::cache::{Cache, CacheItem};
+use crate::common::*;
+use crate::upload::DataType;
+
+/// Storage-agnostic interface to the upload cache.
+///
+/// `upload_data` goes through this trait instead of touching [`Cache`]'s
+/// in-memory map directly, so a large mint can swap the default JSON-file
+/// cache for a backend that persists link updates incrementally instead of
+/// rewriting the whole file on every checkpoint. `process_upload` itself
+/// still reads and updates [`Cache`]'s in-memory map directly when it decides
+/// what to upload and in its final sanity check.
+pub trait CacheRepo {
+    /// Returns the cache item for `asset_id`, if any.
+    fn get(&self, asset_id: &str) -> Result<Option<CacheItem>>;
+    /// Inserts or replaces the cache item for `asset_id`.
+    fn insert(&mut self, asset_id: &str, item: CacheItem) -> Result<()>;
+    /// Updates the media or metadata link of an existing item.
+    fn update_link(&mut self, asset_id: &str, data_type: DataType, link: String) -> Result<()>;
+    /// Persists pending changes (checkpoint).
+    fn sync(&mut self) -> Result<()>;
+}
+
+/// Which [`CacheRepo`] backend `process_upload` checkpoints through, taken
+/// from `UploadArgs::cache_backend`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheBackend {
+    /// Default: every checkpoint rewrites the whole JSON cache file.
+    JsonFile,
+    /// Embedded `sled` store: checkpoints are incremental writes, for
+    /// collections too large to comfortably rewrite on every checkpoint.
+    Sled,
+}
+
+impl FromStr for CacheBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(CacheBackend::JsonFile),
+            "sled" => Ok(CacheBackend::Sled),
+            other => Err(anyhow!(
+                "Unknown cache backend '{}', expected 'json' or 'sled'",
+                other
+            )),
+        }
+    }
+}
+
+/// Opens the [`CacheRepo`] selected by `backend`. `sled_path` is only used
+/// when `backend` is [`CacheBackend::Sled`].
+pub fn open_cache_repo<'a>(
+    backend: CacheBackend,
+    cache: &'a mut Cache,
+    sled_path: &str,
+) -> Result<Box<dyn CacheRepo + 'a>> {
+    match backend {
+        CacheBackend::JsonFile => Ok(Box::new(JsonFileCacheRepo::new(cache))),
+        CacheBackend::Sled => Ok(Box::new(SledCacheRepo::open(sled_path, cache)?)),
+    }
+}
+
+/// Default [`CacheRepo`], backed by the existing JSON-file [`Cache`].
+/// Behavior is unchanged from before this trait existed: every `sync`
+/// rewrites the whole file.
+pub struct JsonFileCacheRepo<'a> {
+    cache: &'a mut Cache,
+}
+
+impl<'a> JsonFileCacheRepo<'a> {
+    pub fn new(cache: &'a mut Cache) -> Self {
+        JsonFileCacheRepo { cache }
+    }
+}
+
+impl<'a> CacheRepo for JsonFileCacheRepo<'a> {
+    fn get(&self, asset_id: &str) -> Result<Option<CacheItem>> {
+        Ok(self.cache.items.0.get(asset_id).cloned())
+    }
+
+    fn insert(&mut self, asset_id: &str, item: CacheItem) -> Result<()> {
+        self.cache.items.0.insert(asset_id.to_string(), item);
+        Ok(())
+    }
+
+    fn update_link(&mut self, asset_id: &str, data_type: DataType, link: String) -> Result<()> {
+        let item = self
+            .cache
+            .items
+            .0
+            .get_mut(asset_id)
+            .ok_or_else(|| anyhow!("Failed to get cache item for '{}'", asset_id))?;
+
+        match data_type {
+            DataType::Media => item.media_link = link,
+            DataType::Metadata => item.metadata_link = link,
+        }
+
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        self.cache.sync_file()
+    }
+}
+
+/// [`CacheRepo`] that checkpoints through an embedded `sled` store instead of
+/// rewriting the whole JSON cache file on every checkpoint.
+///
+/// Reads still go through the in-memory [`Cache`] (kept as the single source
+/// of truth for `process_upload`'s own bookkeeping), but every `insert`/
+/// `update_link` is mirrored into `sled` as an incremental write.
+/// `sync` only has to flush `sled` to disk; the full-file JSON
+/// rewrite happens once, at the very end of `process_upload`, instead of on
+/// every checkpoint.
+pub struct SledCacheRepo<'a> {
+    db: sled::Db,
+    cache: &'a mut Cache,
+}
+
+impl<'a> SledCacheRepo<'a> {
+    pub fn open(path: &str, cache: &'a mut Cache) -> Result<Self> {
+        Ok(SledCacheRepo {
+            db: sled::open(path)?,
+            cache,
+        })
+    }
+}
+
+impl<'a> CacheRepo for SledCacheRepo<'a> {
+    fn get(&self, asset_id: &str) -> Result<Option<CacheItem>> {
+        Ok(self.cache.items.0.get(asset_id).cloned())
+    }
+
+    fn insert(&mut self, asset_id: &str, item: CacheItem) -> Result<()> {
+        let bytes = serde_json::to_vec(&item)?;
+        self.db.insert(asset_id, bytes)?;
+        self.cache.items.0.insert(asset_id.to_string(), item);
+        Ok(())
+    }
+
+    fn update_link(&mut self, asset_id: &str, data_type: DataType, link: String) -> Result<()> {
+        let item = self
+            .cache
+            .items
+            .0
+            .get_mut(asset_id)
+            .ok_or_else(|| anyhow!("Failed to get cache item for '{}'", asset_id))?;
+
+        match data_type {
+            DataType::Media => item.media_link = link,
+            DataType::Metadata => item.metadata_link = link,
+        }
+
+        let bytes = serde_json::to_vec(item)?;
+        self.db.insert(asset_id, bytes)?;
+
+        Ok(())
+    }
+
+    fn sync(&mut self) -> Result<()> {
+        // inserts are written to sled as they happen; flush forces them out
+        // to disk before we report a checkpoint
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cache_backend_from_str_parses_known_backends() {
+        assert_eq!(CacheBackend::from_str("json").unwrap(), CacheBackend::JsonFile);
+        assert_eq!(CacheBackend::from_str("sled").unwrap(), CacheBackend::Sled);
+    }
+
+    #[test]
+    fn cache_backend_from_str_rejects_unknown_backends() {
+        assert!(CacheBackend::from_str("yaml").is_err());
+    }
+}
@@ -1,25 +1,41 @@
 use console::style;
 use futures::future::select_all;
+use rand::Rng;
 use std::{
     borrow::Borrow,
     cmp,
     collections::HashSet,
     ffi::OsStr,
+    future::Future,
+    pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::{Duration, Instant},
 };
+use tracing::Instrument;
 
 use crate::cache::{load_cache, Cache};
+use crate::cache::repo::{open_cache_repo, CacheBackend, CacheRepo};
 use crate::common::*;
 use crate::config::get_config_data;
 use crate::constants::PARALLEL_LIMIT;
 use crate::upload::storage::*;
+use crate::upload::telemetry;
 use crate::upload::*;
 use crate::utils::*;
 use crate::validate::format::Metadata;
 
+/// Base delay before the first retry of a failed upload.
+const RETRY_BASE_DELAY_MS: u64 = 500;
+/// Upper bound on the backoff delay between retries.
+const RETRY_MAX_DELAY_MS: u64 = 15_000;
+/// Number of attempts (including the first one) before an asset is given up on.
+const MAX_ATTEMPTS: u32 = 5;
+/// How long a single upload is allowed to run before it is treated as a failure.
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub struct UploadArgs {
     pub assets_dir: String,
     pub config: String,
@@ -27,6 +43,15 @@ pub struct UploadArgs {
     pub rpc_url: Option<String>,
     pub cache: String,
     pub interrupted: Arc<AtomicBool>,
+    /// OpenTelemetry OTLP collector endpoint to export upload spans to, e.g.
+    /// `http://localhost:4317`. Without it no spans are emitted; with it,
+    /// `RUST_LOG` filters which spans are exported.
+    pub otlp_endpoint: Option<String>,
+    /// Which [`CacheRepo`] backend to checkpoint uploads through. Defaults to
+    /// [`CacheBackend::JsonFile`]; large mints can opt into
+    /// [`CacheBackend::Sled`] to avoid rewriting the whole cache file on
+    /// every checkpoint.
+    pub cache_backend: CacheBackend,
 }
 
 pub struct AssetType {
@@ -35,7 +60,13 @@ pub struct AssetType {
     pub animation: Vec<usize>,
 }
 
+#[tracing::instrument(
+    skip_all,
+    fields(assets = tracing::field::Empty, storage_method = tracing::field::Empty)
+)]
 pub async fn process_upload(args: UploadArgs) -> Result<()> {
+    telemetry::init_tracing(args.otlp_endpoint.as_deref())?;
+
     let sugar_config = sugar_setup(args.keypair, args.rpc_url)?;
     let config_data = get_config_data(&args.config)?;
 
@@ -51,18 +82,24 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
     pb.set_message("Reading files...");
 
     let asset_pairs = get_asset_pairs(&args.assets_dir)?;
+    tracing::Span::current().record("assets", &asset_pairs.len());
     // creates/loads the cache
     let mut cache = load_cache(&args.cache, true)?;
+    // only used when `args.cache_backend` is `CacheBackend::Sled`
+    let sled_cache_path = format!("{}.sled", args.cache);
 
-    // list of indices to upload
-    // 0: image
-    // 1: metadata
+    // indices of the assets that need (re-)uploading, split by file kind
     let mut indices = AssetType {
         image: Vec::new(),
         metadata: Vec::new(),
         animation: Vec::new(),
     };
 
+    // content types sniffed once here and reused by `upload_data`, instead of
+    // sniffing (and re-reading) each file a second time during upload
+    let mut media_content_types: HashMap<String, String> = HashMap::new();
+    let mut animation_content_types: HashMap<String, String> = HashMap::new();
+
     for (index, pair) in &asset_pairs {
         match cache.items.0.get_mut(&index.to_string()) {
             Some(item) => {
@@ -158,6 +195,18 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
                 return Err(error);
             }
         }
+
+        // sniffs and validates the media content type up front, so a
+        // collection mixing extensions or using an unsupported format fails
+        // here rather than after a partial upload; the sniffed type is saved
+        // and handed to `upload_data` instead of sniffing the file again
+        let media_content_type = validate_content_type(Path::new(&pair.media))?;
+        media_content_types.insert(index.to_string(), media_content_type);
+
+        if let Some(animation) = &pair.animation {
+            let animation_content_type = validate_content_type(Path::new(animation))?;
+            animation_content_types.insert(index.to_string(), animation_content_type);
+        }
     }
 
     pb.finish_and_clear();
@@ -206,6 +255,8 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
         pb.set_message("Connecting...");
 
         let storage = storage::initialize(&sugar_config, &config_data).await?;
+        tracing::Span::current()
+            .record("storage_method", &tracing::field::debug(&config_data.upload_method));
 
         pb.finish_with_message("Connected");
 
@@ -214,8 +265,8 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
                 &sugar_config,
                 &asset_pairs,
                 vec![
-                    (DataType::Media, &indices.0),
-                    (DataType::Metadata, &indices.1),
+                    (DataType::Media, &indices.image),
+                    (DataType::Metadata, &indices.metadata),
                 ],
             )
             .await?;
@@ -240,22 +291,22 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
 
         if !indices.image.is_empty() {
             errors.extend(
-                handler
-                    .upload_data(
-                        &sugar_config,
-                        &asset_pairs,
-                        &mut cache,
-                        &indices.image,
-                        DataType::Image,
-                        args.interrupted.clone(),
-                    )
-                    .await?,
+                upload_data(
+                    &asset_pairs,
+                    open_cache_repo(args.cache_backend, &mut cache, &sled_cache_path)?.as_mut(),
+                    &indices.image,
+                    UploadKind::Image,
+                    &media_content_types,
+                    storage.borrow(),
+                    args.interrupted.clone(),
+                )
+                .await?,
             );
 
             // updates the list of metadata indices since the image upload
             // might fail - removes any index that the image upload failed
             if !indices.metadata.is_empty() {
-                for index in indices.image {
+                for index in indices.image.clone() {
                     let item = cache.items.0.get(&index.to_string()).unwrap();
 
                     if item.image_link.is_empty() {
@@ -283,9 +334,10 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
             errors.extend(
                 upload_data(
                     &asset_pairs,
-                    &mut cache,
-                    &indices.0,
-                    DataType::Media,
+                    open_cache_repo(args.cache_backend, &mut cache, &sled_cache_path)?.as_mut(),
+                    &indices.animation,
+                    UploadKind::Animation,
+                    &animation_content_types,
                     storage.borrow(),
                     args.interrupted.clone(),
                 )
@@ -325,9 +377,10 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
             errors.extend(
                 upload_data(
                     &asset_pairs,
-                    &mut cache,
-                    &indices.1,
-                    DataType::Metadata,
+                    open_cache_repo(args.cache_backend, &mut cache, &sled_cache_path)?.as_mut(),
+                    &indices.metadata,
+                    UploadKind::Metadata,
+                    &media_content_types,
                     storage.borrow(),
                     args.interrupted.clone(),
                 )
@@ -406,16 +459,39 @@ pub async fn process_upload(args: UploadArgs) -> Result<()> {
     Ok(())
 }
 
+/// Which physical file a call to [`upload_data`] uploads. Image and
+/// animation both map to [`DataType::Media`] as far as `StorageMethod`/
+/// `CacheRepo` are concerned (it's the same link slot's counterpart on-chain
+/// metadata doesn't distinguish them either), but they are different files
+/// with different cache hashes - `upload_data` needs to know which one this
+/// call is for so dedup and path selection don't collide across passes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UploadKind {
+    Image,
+    Animation,
+    Metadata,
+}
+
+impl UploadKind {
+    fn data_type(self) -> DataType {
+        match self {
+            UploadKind::Image | UploadKind::Animation => DataType::Media,
+            UploadKind::Metadata => DataType::Metadata,
+        }
+    }
+}
+
 /// Upload the data to Bundlr.
 async fn upload_data(
     assets: &HashMap<usize, AssetPair>,
-    cache: &mut Cache,
+    cache: &mut dyn CacheRepo,
     indices: &[usize],
-    data_type: DataType,
+    kind: UploadKind,
+    content_types: &HashMap<String, String>,
     storage: &dyn StorageMethod,
     interrupted: Arc<AtomicBool>,
 ) -> Result<Vec<UploadError>> {
-    let mut extension = HashSet::with_capacity(1);
+    let data_type = kind.data_type();
     let mut paths = Vec::new();
 
     for index in indices {
@@ -423,34 +499,18 @@ async fn upload_data(
             Some(asset_index) => asset_index,
             None => return Err(anyhow::anyhow!("Failed to get asset at index {}", index)),
         };
-        // chooses the file path based on the data type
-        let file_path = match data_type {
-            DataType::Media => item.media.clone(),
-            DataType::Metadata => item.metadata.clone(),
+        // chooses the file path based on which file this call uploads
+        let file_path = match kind {
+            UploadKind::Image => item.media.clone(),
+            UploadKind::Animation => item.animation.clone().ok_or_else(|| {
+                anyhow!("Asset at index {} has no animation file to upload", index)
+            })?,
+            UploadKind::Metadata => item.metadata.clone(),
         };
 
-        let path = Path::new(&file_path);
-        let ext = path
-            .extension()
-            .and_then(OsStr::to_str)
-            .expect("Failed to convert extension from unicode");
-        extension.insert(String::from(ext));
-
         paths.push(file_path);
     }
 
-    // validates that all files have the same extension
-    let extension = if extension.len() == 1 {
-        extension.iter().next().unwrap()
-    } else {
-        return Err(anyhow!("Invalid file extension: {:?}", extension));
-    };
-
-    let content_type = match data_type {
-        DataType::Media => format!("image/{}", extension),
-        DataType::Metadata => "application/json".to_string(),
-    };
-
     // uploading data
 
     println!("\nSending data: (Ctrl+C to abort)");
@@ -469,73 +529,116 @@ async fn upload_data(
                 .expect("Failed to convert path to unicode."),
         );
 
-        let cache_item = match cache.items.0.get(&asset_id) {
+        let cache_item = match cache.get(&asset_id)? {
             Some(item) => item,
             None => return Err(anyhow!("Failed to get config item at index {}", asset_id)),
         };
 
+        // media content types are sniffed once, up front in `process_upload`,
+        // and handed down here instead of re-reading the file a second time
+        let content_type = match kind {
+            UploadKind::Image | UploadKind::Animation => content_types
+                .get(&asset_id)
+                .cloned()
+                .ok_or_else(|| anyhow!("Missing pre-validated content type for '{}'", asset_id))?,
+            UploadKind::Metadata => "application/json".to_string(),
+        };
+
         tasks.push(AssetInfo {
             asset_id: asset_id.to_string(),
             file_path: String::from(path.to_str().expect("Failed to parse path from unicode.")),
             media_link: cache_item.media_link.clone(),
             data_type: data_type.clone(),
-            content_type: content_type.clone(),
+            content_type,
         });
     }
 
+    // content-hash dedup: several indices can share the exact same file (reused
+    // artwork, 1-of-N traits), so we only upload one representative per unique
+    // hash and copy its link to the other indices ("followers") once it lands
+    let mut by_hash: HashMap<String, Vec<AssetInfo>> = HashMap::new();
+
+    for task in tasks.drain(..) {
+        let hash = content_hash(cache, &task.asset_id, kind)?;
+        by_hash.entry(hash).or_insert_with(Vec::new).push(task);
+    }
+
+    let mut followers: HashMap<String, Vec<String>> = HashMap::new();
+    let mut tasks = Vec::with_capacity(by_hash.len());
+
+    for (_hash, mut group) in by_hash {
+        let representative = group.remove(0);
+
+        if !group.is_empty() {
+            followers.insert(
+                representative.asset_id.clone(),
+                group.into_iter().map(|task| task.asset_id).collect(),
+            );
+        }
+
+        tasks.push(representative);
+    }
+
     let mut handles = Vec::new();
 
     for task in tasks.drain(0..cmp::min(tasks.len(), PARALLEL_LIMIT)) {
-        handles.push(storage.upload_data(task));
+        handles.push(spawn_upload(storage, task, None));
     }
 
     let mut errors = Vec::new();
+    // number of attempts made so far for each asset, keyed by asset id - used
+    // to compute the backoff delay and to give up after MAX_ATTEMPTS
+    let mut attempts: HashMap<String, u32> = HashMap::new();
 
     while !interrupted.load(Ordering::SeqCst) && !handles.is_empty() {
-        match select_all(handles).await {
-            (Ok(res), _index, remaining) => {
-                // independently if the upload was successful or not
-                // we continue to try the remaining ones
-                handles = remaining;
-
-                if res.is_ok() {
-                    let val = res?;
-                    let link = val.clone().1;
-                    // cache item to update
-                    let item = cache.items.0.get_mut(&val.0).unwrap();
-
-                    match data_type {
-                        DataType::Media => item.media_link = link,
-                        DataType::Metadata => item.metadata_link = link,
+        let ((asset_info, result), _index, remaining) = select_all(handles).await;
+        handles = remaining;
+
+        match result {
+            Ok((asset_id, link)) => {
+                // cache item to update
+                cache.update_link(&asset_id, data_type.clone(), link.clone())?;
+
+                // followers share the exact same content, so they just get a
+                // copy of the representative's link - no upload needed
+                let mut uploaded = 1;
+
+                if let Some(follower_ids) = followers.get(&asset_id) {
+                    for follower_id in follower_ids {
+                        cache.update_link(follower_id, data_type.clone(), link.clone())?;
                     }
-                    // updates the progress bar
-                    pb.inc(1);
+                    uploaded += follower_ids.len();
+                }
+
+                // updates the progress bar
+                pb.inc(uploaded as u64);
+            }
+            Err(err) => {
+                let attempt = attempts.entry(asset_info.asset_id.clone()).or_insert(0);
+                *attempt += 1;
+
+                if *attempt < MAX_ATTEMPTS {
+                    // re-enqueues the asset with an exponential backoff delay
+                    // instead of giving up on the first failure
+                    let delay = backoff_delay(*attempt);
+                    handles.push(spawn_upload(storage, asset_info, Some(delay)));
                 } else {
-                    // user will need to retry the upload
                     errors.push(UploadError::SendDataFailed(format!(
-                        "Upload error: {:?}",
-                        res.err().unwrap()
+                        "Upload error (giving up after {} attempts): {:?}",
+                        attempt, err
                     )));
                 }
             }
-            (Err(err), _index, remaining) => {
-                errors.push(UploadError::SendDataFailed(format!(
-                    "Upload error: {:?}",
-                    err
-                )));
-                // ignoring all errors
-                handles = remaining;
-            }
         }
 
         if !tasks.is_empty() {
             // if we are half way through, let spawn more transactions
             if (PARALLEL_LIMIT - handles.len()) > (PARALLEL_LIMIT / 2) {
                 // syncs cache (checkpoint)
-                cache.sync_file()?;
+                cache.sync()?;
 
                 for task in tasks.drain(0..cmp::min(tasks.len(), PARALLEL_LIMIT / 2)) {
-                    handles.push(storage.upload_data(task));
+                    handles.push(spawn_upload(storage, task, None));
                 }
             }
         }
@@ -550,8 +653,253 @@ async fn upload_data(
         pb.finish_with_message(format!("{}", style("Upload successful ").green().bold()));
     }
 
-    // makes sure the cache file is updated
-    cache.sync_file()?;
+    // makes sure the cache is checkpointed
+    cache.sync()?;
 
     Ok(errors)
 }
+
+/// Uploads a single asset, applying `delay` before starting (used to space
+/// out retries) and bounding the upload itself with [`UPLOAD_TIMEOUT`]. The
+/// original [`AssetInfo`] is returned alongside the result so the caller can
+/// re-enqueue it on failure without having kept its own copy around.
+fn spawn_upload<'a>(
+    storage: &'a dyn StorageMethod,
+    task: AssetInfo,
+    delay: Option<Duration>,
+) -> Pin<Box<dyn Future<Output = (AssetInfo, Result<(String, String)>)> + 'a>> {
+    let asset_info = task.clone();
+
+    Box::pin(async move {
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+
+        let byte_size = tokio::fs::metadata(&task.file_path)
+            .await
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+
+        let span = tracing::info_span!(
+            "upload_asset",
+            asset_id = %asset_info.asset_id,
+            data_type = ?asset_info.data_type,
+            byte_size,
+        );
+
+        let started = Instant::now();
+        let result = async {
+            let mut handle = storage.upload_data(task);
+
+            match tokio::time::timeout(UPLOAD_TIMEOUT, &mut handle).await {
+                Ok(Ok(res)) => res,
+                Ok(Err(join_err)) => Err(anyhow!("Upload task panicked: {}", join_err)),
+                Err(_) => {
+                    // the timeout only stops us from waiting any longer - the
+                    // spawned upload is still running unless we abort it, and
+                    // a retry would otherwise race a second upload against it
+                    handle.abort();
+                    Err(anyhow!(
+                        "Upload of '{}' timed out after {:?}",
+                        asset_info.asset_id,
+                        UPLOAD_TIMEOUT
+                    ))
+                }
+            }
+        }
+        .instrument(span.clone())
+        .await;
+
+        span.in_scope(|| {
+            tracing::info!(
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                success = result.is_ok(),
+                "upload finished"
+            );
+        });
+
+        (asset_info, result)
+    })
+}
+
+/// MIME types the upload pipeline accepts for media uploads. Anything else is
+/// rejected during validation, before any upload is attempted.
+const ALLOWED_MEDIA_MIME_TYPES: &[&str] = &[
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "video/mp4",
+    "model/gltf-binary",
+];
+
+/// Extension fallback for allow-listed types `infer` doesn't reliably
+/// recognize from magic bytes alone: it has no glTF-binary signature at all,
+/// and only detects some `.mp4` profiles. Checked only when sniffing comes up
+/// empty or outside [`ALLOWED_MEDIA_MIME_TYPES`], so it never overrides a
+/// successful sniff.
+const EXTENSION_CONTENT_TYPE_FALLBACK: &[(&str, &str)] =
+    &[("glb", "model/gltf-binary"), ("mp4", "video/mp4")];
+
+/// Sniffs the MIME type of a file from its magic bytes rather than trusting
+/// its extension, so a collection can mix e.g. `.png`/`.jpg` images or use
+/// `.mp4`/`.glb` animations without restriction.
+fn sniff_content_type(path: &Path) -> Result<String> {
+    if let Some(kind) = infer::get_file(path)? {
+        let mime_type = kind.mime_type().to_string();
+
+        if ALLOWED_MEDIA_MIME_TYPES.contains(&mime_type.as_str()) {
+            return Ok(mime_type);
+        }
+    }
+
+    // magic-byte sniffing either found nothing or found a type we don't
+    // allow-list - fall back to the extension for formats we know `infer`
+    // can't reliably detect, instead of rejecting files this pipeline is
+    // explicitly meant to support
+    let extension = path
+        .extension()
+        .and_then(OsStr::to_str)
+        .map(|ext| ext.to_lowercase());
+
+    extension
+        .as_deref()
+        .and_then(|ext| {
+            EXTENSION_CONTENT_TYPE_FALLBACK
+                .iter()
+                .find(|(candidate, _)| *candidate == ext)
+        })
+        .map(|(_, mime_type)| mime_type.to_string())
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not determine the content type of '{}'",
+                path.display()
+            )
+        })
+}
+
+/// Sniffs and validates the content type of a media file against
+/// [`ALLOWED_MEDIA_MIME_TYPES`], failing fast rather than after a partial
+/// upload.
+fn validate_content_type(path: &Path) -> Result<String> {
+    let content_type = sniff_content_type(path)?;
+
+    if !ALLOWED_MEDIA_MIME_TYPES.contains(&content_type.as_str()) {
+        return Err(anyhow!(
+            "Unsupported media type '{}' for file '{}'",
+            content_type,
+            path.display()
+        ));
+    }
+
+    Ok(content_type)
+}
+
+/// Returns the content hash used to dedup uploads of the given asset, keyed
+/// on whichever file `kind` actually uploads: the image hash for
+/// [`UploadKind::Image`], the animation hash for [`UploadKind::Animation`],
+/// or the metadata hash for [`UploadKind::Metadata`]. `DataType::Media`
+/// alone can't disambiguate image from animation - two assets sharing an
+/// image but not an animation (or vice-versa) would otherwise collapse into
+/// one upload and both get the wrong link.
+fn content_hash(cache: &dyn CacheRepo, asset_id: &str, kind: UploadKind) -> Result<String> {
+    let item = cache
+        .get(asset_id)?
+        .ok_or_else(|| anyhow!("Failed to get cache item for '{}'", asset_id))?;
+
+    Ok(match kind {
+        UploadKind::Image => item.image_hash.clone(),
+        UploadKind::Animation => item.animation_hash.clone().unwrap_or_default(),
+        UploadKind::Metadata => item.metadata_hash.clone(),
+    })
+}
+
+/// Computes the exponential backoff delay (with jitter) for the given retry
+/// attempt, capped at [`RETRY_MAX_DELAY_MS`].
+fn backoff_delay(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(10);
+    let exp_delay = RETRY_BASE_DELAY_MS.saturating_mul(1u64 << exponent);
+    let capped = exp_delay.min(RETRY_MAX_DELAY_MS);
+    let jitter = rand::thread_rng().gen_range(0..=capped / 4);
+
+    Duration::from_millis(capped + jitter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn backoff_delay_grows_exponentially_up_to_the_cap() {
+        let first = backoff_delay(1);
+        let second = backoff_delay(2);
+
+        assert!(first.as_millis() >= RETRY_BASE_DELAY_MS as u128);
+        assert!(second.as_millis() >= (RETRY_BASE_DELAY_MS * 2) as u128);
+    }
+
+    #[test]
+    fn backoff_delay_never_exceeds_the_max_plus_jitter() {
+        // a huge attempt count must still saturate at RETRY_MAX_DELAY_MS
+        // instead of overflowing or growing unbounded
+        let delay = backoff_delay(1_000);
+        assert!(delay.as_millis() <= (RETRY_MAX_DELAY_MS + RETRY_MAX_DELAY_MS / 4) as u128);
+    }
+
+    /// Writes `bytes` to a uniquely-named file under the OS temp dir and
+    /// returns its path; the caller is responsible for removing it.
+    fn write_temp_file(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn sniff_content_type_detects_png_from_magic_bytes() {
+        let path = write_temp_file(
+            "sugar_test_sniff.png",
+            &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A],
+        );
+
+        let content_type = sniff_content_type(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(content_type, "image/png");
+    }
+
+    #[test]
+    fn sniff_content_type_falls_back_to_extension_for_glb() {
+        // infer has no glTF-binary signature at all, so this must come from
+        // EXTENSION_CONTENT_TYPE_FALLBACK rather than magic-byte sniffing
+        let path = write_temp_file("sugar_test_sniff.glb", b"not a real glb payload");
+
+        let content_type = sniff_content_type(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(content_type, "model/gltf-binary");
+    }
+
+    #[test]
+    fn sniff_content_type_rejects_unrecognized_files() {
+        let path = write_temp_file("sugar_test_sniff.bin", b"not a real payload");
+
+        let result = sniff_content_type(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn validate_content_type_rejects_disallowed_mime_types() {
+        // a valid zip signature, but zip isn't in ALLOWED_MEDIA_MIME_TYPES
+        let path = write_temp_file(
+            "sugar_test_validate.zip",
+            &[b'P', b'K', 0x03, 0x04, 0, 0, 0, 0],
+        );
+
+        let result = validate_content_type(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(result.is_err());
+    }
+}
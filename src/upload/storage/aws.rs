@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use aws_sdk_s3::{
+    model::ObjectCannedAcl, types::ByteStream, Client, Credentials, Endpoint, Region,
+};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::common::*;
+use crate::config::SugarConfig;
+use crate::upload::storage::StorageMethod;
+use crate::upload::{AssetInfo, AssetPair, DataType};
+
+/// Configuration for the self-hosted S3/MinIO backend, set under the
+/// `aws_s3` section of the Sugar config file.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AwsS3Config {
+    pub bucket: String,
+    pub region: String,
+    /// Custom endpoint URL (e.g. a MinIO deployment). Leave unset to use
+    /// AWS' default endpoint for `region`.
+    pub endpoint: Option<String>,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+pub struct AwsS3Method {
+    client: Client,
+    bucket: String,
+    region: String,
+    endpoint: Option<String>,
+}
+
+impl AwsS3Method {
+    pub async fn new(config: &AwsS3Config) -> Result<AwsS3Method> {
+        let credentials = Credentials::new(
+            &config.access_key_id,
+            &config.secret_access_key,
+            None,
+            None,
+            "sugar",
+        );
+
+        let mut loader = aws_config::from_env()
+            .region(Region::new(config.region.clone()))
+            .credentials_provider(credentials);
+
+        if let Some(endpoint) = &config.endpoint {
+            loader = loader.endpoint_resolver(Endpoint::immutable(endpoint.parse()?));
+        }
+
+        let client = Client::new(&loader.load().await);
+
+        Ok(AwsS3Method {
+            client,
+            bucket: config.bucket.clone(),
+            region: config.region.clone(),
+            endpoint: config.endpoint.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl StorageMethod for AwsS3Method {
+    async fn prepare(
+        &self,
+        _sugar_config: &SugarConfig,
+        _asset_pairs: &HashMap<usize, AssetPair>,
+        _upload_indices: Vec<(DataType, &[usize])>,
+    ) -> Result<()> {
+        // verifies that the bucket exists and is reachable with the configured
+        // credentials before we start uploading any assets; HeadBucket does
+        // not check write permission
+        self.client
+            .head_bucket()
+            .bucket(&self.bucket)
+            .send()
+            .await
+            .map_err(|err| anyhow!("Failed to access S3 bucket '{}': {}", self.bucket, err))?;
+
+        Ok(())
+    }
+
+    fn upload_data(&self, asset_info: AssetInfo) -> JoinHandle<Result<(String, String)>> {
+        let client = self.client.clone();
+        let bucket = self.bucket.clone();
+        let region = self.region.clone();
+        let endpoint = self.endpoint.clone();
+
+        tokio::spawn(async move {
+            let data = tokio::fs::read(&asset_info.file_path).await?;
+            let key = match asset_info.data_type {
+                DataType::Metadata => format!("{}.json", asset_info.asset_id),
+                _ => asset_info.asset_id.clone(),
+            };
+
+            client
+                .put_object()
+                .bucket(&bucket)
+                .key(&key)
+                .body(ByteStream::from(data))
+                .content_type(&asset_info.content_type)
+                // the request asks for the public object URL to be stored in
+                // the cache, so the object itself needs to be publicly
+                // readable - without this the bucket's default (private)
+                // ACL would make that URL 403 for minters
+                .acl(ObjectCannedAcl::PublicRead)
+                .send()
+                .await
+                .map_err(|err| anyhow!("Failed to upload '{}' to S3: {}", key, err))?;
+
+            let link = object_url(endpoint.as_deref(), &bucket, &region, &key);
+
+            Ok((asset_info.asset_id, link))
+        })
+    }
+}
+
+/// Builds the public URL for an uploaded object: the custom `endpoint` if one
+/// is configured (e.g. a MinIO deployment), otherwise AWS' virtual-hosted
+/// URL for `region` - the region has to be explicit, since the bucket-only
+/// form only resolves correctly for `us-east-1`.
+fn object_url(endpoint: Option<&str>, bucket: &str, region: &str, key: &str) -> String {
+    match endpoint {
+        Some(endpoint) => format!("{}/{}/{}", endpoint.trim_end_matches('/'), bucket, key),
+        None => format!("https://{}.s3.{}.amazonaws.com/{}", bucket, region, key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn object_url_uses_region_specific_host_by_default() {
+        let url = object_url(None, "my-bucket", "eu-west-1", "0.png");
+        assert_eq!(url, "https://my-bucket.s3.eu-west-1.amazonaws.com/0.png");
+    }
+
+    #[test]
+    fn object_url_prefers_custom_endpoint_when_configured() {
+        let url = object_url(Some("https://minio.example.com/"), "my-bucket", "us-east-1", "0.png");
+        assert_eq!(url, "https://minio.example.com/my-bucket/0.png");
+    }
+}
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use bundlr_sdk::{tags::Tag, Bundlr, Ed25519Signer};
+use std::collections::HashMap;
+use tokio::task::JoinHandle;
+
+use crate::common::*;
+use crate::config::{ConfigData, SugarConfig};
+use crate::upload::storage::StorageMethod;
+use crate::upload::{AssetInfo, AssetPair, DataType};
+
+const BUNDLR_ENDPOINT: &str = "https://node1.bundlr.network";
+
+pub struct BundlrMethod {
+    bundlr: Bundlr<Ed25519Signer>,
+}
+
+impl BundlrMethod {
+    pub async fn new(sugar_config: &SugarConfig, _config_data: &ConfigData) -> Result<Self> {
+        let signer = Ed25519Signer::from_keypair(&sugar_config.keypair);
+
+        Ok(BundlrMethod {
+            bundlr: Bundlr::new(BUNDLR_ENDPOINT.to_string(), "solana".to_string(), signer),
+        })
+    }
+
+    /// Total size, in bytes, of every file scheduled for upload across
+    /// `upload_indices`.
+    async fn total_upload_size(
+        &self,
+        asset_pairs: &HashMap<usize, AssetPair>,
+        upload_indices: &[(DataType, &[usize])],
+    ) -> Result<u64> {
+        let mut total = 0u64;
+
+        for (data_type, indices) in upload_indices {
+            for index in *indices {
+                let pair = asset_pairs
+                    .get(index)
+                    .ok_or_else(|| anyhow!("Failed to get asset at index {}", index))?;
+
+                let path = match data_type {
+                    DataType::Media => &pair.media,
+                    DataType::Metadata => &pair.metadata,
+                };
+
+                total += tokio::fs::metadata(path).await?.len();
+            }
+        }
+
+        Ok(total)
+    }
+}
+
+#[async_trait]
+impl StorageMethod for BundlrMethod {
+    async fn prepare(
+        &self,
+        _sugar_config: &SugarConfig,
+        asset_pairs: &HashMap<usize, AssetPair>,
+        upload_indices: Vec<(DataType, &[usize])>,
+    ) -> Result<()> {
+        // fails fast if the node isn't funded for this upload, instead of
+        // letting individual asset uploads fail mid-run once the balance
+        // runs out
+        let total_bytes = self.total_upload_size(asset_pairs, &upload_indices).await?;
+
+        let price = self
+            .bundlr
+            .get_price(total_bytes as u32)
+            .await
+            .map_err(|err| anyhow!("Failed to get Bundlr price quote: {}", err))?;
+
+        let balance = self
+            .bundlr
+            .get_balance(&self.bundlr.get_public_key())
+            .await
+            .map_err(|err| anyhow!("Failed to get Bundlr balance: {}", err))?;
+
+        if balance < price {
+            return Err(anyhow!(
+                "Insufficient Bundlr balance: need {} to upload {} byte(s) but only have {} - fund the node before uploading",
+                price,
+                total_bytes,
+                balance
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn upload_data(&self, asset_info: AssetInfo) -> JoinHandle<Result<(String, String)>> {
+        let bundlr = self.bundlr.clone();
+
+        tokio::spawn(async move {
+            let data = tokio::fs::read(&asset_info.file_path).await?;
+            let tags = vec![Tag::new("Content-Type", &asset_info.content_type)];
+
+            let tx = bundlr.create_transaction_with_tags(data, tags);
+            let response = bundlr.send_transaction(tx).await?;
+
+            let link = format!("{}/{}", BUNDLR_ENDPOINT, response.id);
+            Ok((asset_info.asset_id, link))
+        })
+    }
+}
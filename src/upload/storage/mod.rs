@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use tokio::task::JoinHandle;
+
+use crate::common::*;
+use crate::config::{ConfigData, SugarConfig, UploadMethod};
+use crate::upload::{AssetInfo, AssetPair, DataType};
+
+mod aws;
+mod bundlr;
+
+pub use aws::{AwsS3Config, AwsS3Method};
+pub use bundlr::BundlrMethod;
+
+/// Common interface implemented by all the supported upload backends.
+#[async_trait]
+pub trait StorageMethod {
+    /// Performs any setup/validation required before assets can be uploaded,
+    /// e.g. checking balances or verifying bucket access.
+    async fn prepare(
+        &self,
+        sugar_config: &SugarConfig,
+        asset_pairs: &HashMap<usize, AssetPair>,
+        upload_indices: Vec<(DataType, &[usize])>,
+    ) -> Result<()>;
+
+    /// Uploads a single asset and returns its `(asset_id, link)` pair.
+    fn upload_data(&self, asset_info: AssetInfo) -> JoinHandle<Result<(String, String)>>;
+}
+
+/// Selects and initializes the upload backend configured in the Sugar config
+/// file.
+pub async fn initialize(
+    sugar_config: &SugarConfig,
+    config_data: &ConfigData,
+) -> Result<Box<dyn StorageMethod>> {
+    match config_data.upload_method {
+        UploadMethod::Bundlr => Ok(Box::new(
+            BundlrMethod::new(sugar_config, config_data).await?,
+        )),
+        UploadMethod::AwsS3 => {
+            let config = config_data
+                .aws_s3
+                .as_ref()
+                .ok_or_else(|| anyhow!("Missing `aws_s3` configuration section"))?;
+
+            Ok(Box::new(AwsS3Method::new(config).await?))
+        }
+    }
+}
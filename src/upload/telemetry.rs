@@ -0,0 +1,72 @@
+use tracing_subscriber::{prelude::*, EnvFilter};
+
+use crate::common::*;
+
+/// Installs a `tracing` subscriber for the upload pipeline.
+///
+/// This is purely additive instrumentation: existing `println!`/progress-bar
+/// output is untouched, and nothing is emitted unless an OTLP endpoint is
+/// configured: the only output layer is the OpenTelemetry exporter. When
+/// `otlp_endpoint` is provided, spans are exported to that collector so
+/// operators can build per-asset and per-storage-call latency histograms;
+/// `RUST_LOG` filters which spans are exported (default `info`, which covers
+/// the per-asset upload spans).
+///
+/// Installing a global subscriber can only ever fail because one is already
+/// installed (a second upload running in-process, or an embedding app that
+/// set up its own), and setting up the OTLP pipeline can fail if the
+/// collector endpoint is misconfigured or unreachable. Since this
+/// instrumentation is additive and off by default, both failures are logged
+/// and swallowed rather than propagated - the upload must not abort just
+/// because tracing didn't get to install itself.
+pub fn init_tracing(otlp_endpoint: Option<&str>) -> Result<()> {
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let registry = tracing_subscriber::registry().with(env_filter);
+
+    // a misconfigured/unreachable collector endpoint must not abort the
+    // upload - tracing is additive and off by default, so installing the
+    // OTLP pipeline is best-effort just like the subscriber install below
+    let tracer = otlp_endpoint.and_then(|endpoint| match build_otlp_tracer(endpoint) {
+        Ok(tracer) => Some(tracer),
+        Err(err) => {
+            eprintln!("Warning: could not install OTLP tracing pipeline: {}", err);
+            None
+        }
+    });
+
+    let result = match tracer {
+        Some(tracer) => registry
+            .with(tracing_opentelemetry::layer().with_tracer(tracer))
+            .try_init(),
+        None => registry.try_init(),
+    };
+
+    if let Err(err) = result {
+        eprintln!("Warning: could not install tracing subscriber: {}", err);
+    }
+
+    Ok(())
+}
+
+/// Builds the OTLP tracer that exports spans to the collector at `endpoint`.
+/// Split out from [`init_tracing`] so a failure here (e.g. the endpoint isn't
+/// reachable) can be logged and swallowed the same way a subscriber-install
+/// failure already is, instead of propagating and aborting the upload.
+fn build_otlp_tracer(endpoint: &str) -> Result<opentelemetry::sdk::trace::Tracer> {
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(opentelemetry::sdk::trace::config().with_resource(
+            opentelemetry::sdk::Resource::new(vec![opentelemetry::KeyValue::new(
+                "service.name",
+                "sugar",
+            )]),
+        ))
+        .install_batch(opentelemetry::runtime::Tokio)?;
+
+    Ok(tracer)
+}